@@ -1,6 +1,9 @@
 use std::mem;
 
 use criterion::{criterion_group, criterion_main, Bencher, BenchmarkId, Criterion};
+use rand::rngs::SmallRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
 
 use comparison_of_maps::*;
 
@@ -14,7 +17,49 @@ fn fill<V: Default, M: Map<u32, V> + Default>(count: u32) -> M {
     map
 }
 
-fn run_one<V, M>(b: &mut Bencher<'_>, n: u32)
+fn small_rng() -> SmallRng {
+    let seed = u64::from_le_bytes(*b"mapbench");
+    SmallRng::seed_from_u64(seed)
+}
+
+#[derive(Clone, Copy)]
+struct Workload {
+    label: &'static str,
+    miss_rate: f64,
+    randomized: bool,
+}
+
+const WORKLOADS: &[Workload] = &[
+    Workload {
+        label: "hits",
+        miss_rate: 0.0,
+        randomized: false,
+    },
+    Workload {
+        label: "misses=25%",
+        miss_rate: 0.25,
+        randomized: false,
+    },
+    Workload {
+        label: "shuffled",
+        miss_rate: 0.0,
+        randomized: true,
+    },
+];
+
+fn probes_for(n: u32, workload: Workload) -> Vec<u32> {
+    let miss_count =
+        (f64::from(n) * workload.miss_rate / (1.0 - workload.miss_rate)).round() as u32;
+    let mut probes: Vec<u32> = (0..n).chain(n..n + miss_count).collect();
+
+    if workload.randomized {
+        probes.shuffle(&mut small_rng());
+    }
+
+    probes
+}
+
+fn run_one<V, M>(b: &mut Bencher<'_>, n: u32, probes: &[u32])
 where
     V: Default,
     M: Map<u32, V> + Default,
@@ -22,39 +67,53 @@ where
     let map = fill::<V, M>(n);
     let mut index = 0;
     b.iter(|| {
-        index += 1;
-        map.find(index % n)
+        index = (index + 1) % probes.len();
+        map.find(probes[index])
     });
 }
 
 fn run<V: Default>(c: &mut Criterion) {
-    let mut group = c.benchmark_group(&format!("size={}", mem::size_of::<V>()));
-
-    for n in [1u32, 5, 10, 15, 30, 50].iter().cloned() {
-        group.bench_with_input(BenchmarkId::new("linear map", n), &n, |b, n| {
-            run_one::<V, LinearMap<_, _>>(b, *n)
-        });
-        group.bench_with_input(BenchmarkId::new("binary map", n), &n, |b, n| {
-            run_one::<V, BinaryMap<_, _>>(b, *n)
-        });
-        group.bench_with_input(BenchmarkId::new("kv map", n), &n, |b, n| {
-            run_one::<V, KvMap<_, _>>(b, *n)
-        });
-        group.bench_with_input(BenchmarkId::new("simd8 map", n), &n, |b, n| {
-            run_one::<V, SimdMap8<_, _>>(b, *n)
-        });
-        group.bench_with_input(BenchmarkId::new("simd16 map", n), &n, |b, n| {
-            run_one::<V, SimdMap16<_, _>>(b, *n)
-        });
-        group.bench_with_input(BenchmarkId::new("hash map", n), &n, |b, n| {
-            run_one::<V, HashMap<_, _>>(b, *n)
-        });
-        group.bench_with_input(BenchmarkId::new("btree map", n), &n, |b, n| {
-            run_one::<V, BTreeMap<_, _>>(b, *n)
-        });
-    }
+    for workload in WORKLOADS.iter().cloned() {
+        let mut group = c.benchmark_group(&format!(
+            "size={}/{}",
+            mem::size_of::<V>(),
+            workload.label
+        ));
 
-    group.finish();
+        for n in [1u32, 5, 10, 15, 30, 50].iter().cloned() {
+            let probes = probes_for(n, workload);
+
+            group.bench_with_input(BenchmarkId::new("linear map", n), &n, |b, _| {
+                run_one::<V, LinearMap<_, _>>(b, n, &probes)
+            });
+            group.bench_with_input(BenchmarkId::new("binary map", n), &n, |b, _| {
+                run_one::<V, BinaryMap<_, _>>(b, n, &probes)
+            });
+            group.bench_with_input(BenchmarkId::new("kv map", n), &n, |b, _| {
+                run_one::<V, KvMap<_, _>>(b, n, &probes)
+            });
+            group.bench_with_input(BenchmarkId::new("index map", n), &n, |b, _| {
+                run_one::<V, IndexMap<_, _>>(b, n, &probes)
+            });
+            group.bench_with_input(BenchmarkId::new("simd map", n), &n, |b, _| {
+                run_one::<V, SimdMap<_, _>>(b, n, &probes)
+            });
+            group.bench_with_input(BenchmarkId::new("vec map", n), &n, |b, _| {
+                run_one::<V, VecMap<_>>(b, n, &probes)
+            });
+            group.bench_with_input(BenchmarkId::new("swiss map", n), &n, |b, _| {
+                run_one::<V, SwissMap<_, _>>(b, n, &probes)
+            });
+            group.bench_with_input(BenchmarkId::new("hash map", n), &n, |b, _| {
+                run_one::<V, HashMap<_, _>>(b, n, &probes)
+            });
+            group.bench_with_input(BenchmarkId::new("btree map", n), &n, |b, _| {
+                run_one::<V, BTreeMap<_, _>>(b, n, &probes)
+            });
+        }
+
+        group.finish();
+    }
 }
 
 fn map(c: &mut Criterion) {
@@ -1,14 +1,22 @@
+use std::cmp::Ordering;
 use std::collections;
-use std::hash::Hash;
+use std::hash::{Hash, Hasher};
+use std::mem;
 
-use fxhash::FxBuildHasher;
-use packed_simd::{u32x16, u32x8};
+use fxhash::{FxBuildHasher, FxHasher};
+use packed_simd::{u32x16, u32x8, u8x16};
 
 pub trait Map<K, V> {
     fn insert(&mut self, key: K, value: V);
     fn find(&self, key: K) -> Option<&V>;
 }
 
+fn hash_of<T: Hash>(key: &T) -> u64 {
+    let mut hasher = FxHasher::default();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
 #[derive(Default)]
 pub struct LinearMap<K, V> {
     data: Vec<(K, V)>,
@@ -27,19 +35,40 @@ impl<K: Eq, V> Map<K, V> for LinearMap<K, V> {
     }
 }
 
-#[derive(Default)]
 pub struct BinaryMap<K, V> {
     data: Vec<(K, V)>,
+    comparator: Box<dyn Fn(&K, &K) -> Ordering>,
+}
+
+impl<K, V> BinaryMap<K, V> {
+    pub fn with_comparator<F>(comparator: F) -> Self
+    where
+        F: Fn(&K, &K) -> Ordering + 'static,
+    {
+        BinaryMap {
+            data: Vec::new(),
+            comparator: Box::new(comparator),
+        }
+    }
+}
+
+impl<K: Ord + 'static, V> Default for BinaryMap<K, V> {
+    fn default() -> Self {
+        BinaryMap::with_comparator(|a: &K, b: &K| a.cmp(b))
+    }
 }
 
-impl<K: Ord, V> Map<K, V> for BinaryMap<K, V> {
+impl<K, V> Map<K, V> for BinaryMap<K, V> {
     fn insert(&mut self, key: K, value: V) {
         self.data.push((key, value));
-        self.data.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        self.data.sort_unstable_by(|a, b| (self.comparator)(&a.0, &b.0));
     }
 
     fn find(&self, key: K) -> Option<&V> {
-        match self.data.binary_search_by(|entry| entry.0.cmp(&key)) {
+        match self
+            .data
+            .binary_search_by(|entry| (self.comparator)(&entry.0, &key))
+        {
             Ok(index) => Some(unsafe { &self.data.get_unchecked(index).1 }),
             Err(_) => None,
         }
@@ -65,68 +94,324 @@ impl<K: Eq, V> Map<K, V> for KvMap<K, V> {
 }
 
 #[derive(Default)]
-pub struct SimdMap16<K, V> {
+pub struct IndexMap<K, V> {
+    entries: Vec<(K, V)>,
+    indices: Vec<Option<usize>>,
+}
+
+impl<K: Eq + Hash, V> IndexMap<K, V> {
+    pub fn get_index(&self, index: usize) -> Option<(&K, &V)> {
+        self.entries.get(index).map(|(key, value)| (key, value))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &(K, V)> {
+        self.entries.iter()
+    }
+
+    fn bucket(&self, hash: u64) -> usize {
+        hash as usize % self.indices.len()
+    }
+
+    fn insert_index(&mut self, hash: u64, position: usize) {
+        let mut bucket = self.bucket(hash);
+
+        loop {
+            match self.indices[bucket] {
+                None => {
+                    self.indices[bucket] = Some(position);
+                    return;
+                }
+                Some(_) => bucket = (bucket + 1) % self.indices.len(),
+            }
+        }
+    }
+
+    fn grow(&mut self) {
+        let capacity = (self.indices.len() * 2).max(16);
+        self.indices = vec![None; capacity];
+
+        let hashes: Vec<u64> = self.entries.iter().map(|(key, _)| hash_of(key)).collect();
+
+        for (position, hash) in hashes.into_iter().enumerate() {
+            self.insert_index(hash, position);
+        }
+    }
+}
+
+impl<K: Eq + Hash, V> Map<K, V> for IndexMap<K, V> {
+    fn insert(&mut self, key: K, value: V) {
+        if (self.entries.len() + 1) * 4 > self.indices.len() * 3 {
+            self.grow();
+        }
+
+        let hash = hash_of(&key);
+        let position = self.entries.len();
+        self.entries.push((key, value));
+        self.insert_index(hash, position);
+    }
+
+    fn find(&self, key: K) -> Option<&V> {
+        if self.indices.is_empty() {
+            return None;
+        }
+
+        let hash = hash_of(&key);
+        let mut bucket = self.bucket(hash);
+
+        loop {
+            match self.indices[bucket] {
+                None => return None,
+                Some(position) if self.entries[position].0 == key => {
+                    return Some(&self.entries[position].1);
+                }
+                Some(_) => bucket = (bucket + 1) % self.indices.len(),
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SimdWidth {
+    Eight,
+    Sixteen,
+}
+
+impl SimdWidth {
+    fn detect() -> Self {
+        #[cfg(target_arch = "x86_64")]
+        {
+            if is_x86_feature_detected!("avx2") {
+                return SimdWidth::Sixteen;
+            }
+        }
+
+        SimdWidth::Eight
+    }
+
+    fn lanes(self) -> usize {
+        match self {
+            SimdWidth::Eight => 8,
+            SimdWidth::Sixteen => 16,
+        }
+    }
+}
+
+pub struct SimdMap<K, V> {
     keys: Vec<K>,
-    next: usize,
     values: Vec<V>,
+    width: SimdWidth,
+}
+
+impl<K, V> Default for SimdMap<K, V> {
+    fn default() -> Self {
+        SimdMap {
+            keys: Vec::new(),
+            values: Vec::new(),
+            width: SimdWidth::detect(),
+        }
+    }
 }
 
-impl<V> Map<u32, V> for SimdMap16<u32, V> {
+impl<V> Map<u32, V> for SimdMap<u32, V> {
     fn insert(&mut self, key: u32, value: V) {
-        if self.next == self.keys.len() {
-            self.keys.extend(&[0; 16]);
+        if self.values.len() == self.keys.len() {
+            self.keys.resize(self.keys.len() + self.width.lanes(), 0);
         }
 
-        self.keys[self.next] = key;
-        self.next += 1;
+        self.keys[self.values.len()] = key;
         self.values.push(value);
     }
 
     fn find(&self, key: u32) -> Option<&V> {
-        for index in (0..self.keys.len()).step_by(16) {
-            let cursor = &self.keys[index..];
-            let mask = u32x16::from_slice_unaligned(cursor).eq(u32x16::splat(key));
-            let zeros = mask.bitmask().trailing_zeros();
+        let len = self.values.len();
+        let index = match self.width {
+            SimdWidth::Eight => find_width8(&self.keys, len, key),
+            SimdWidth::Sixteen => find_width16(&self.keys, len, key),
+        };
 
-            if zeros < 16 {
-                return self.values.get(index + zeros as usize);
-            }
+        index.map(|index| &self.values[index])
+    }
+}
+
+fn find_width16(keys: &[u32], len: usize, key: u32) -> Option<usize> {
+    for index in (0..keys.len()).step_by(16) {
+        let remaining = len.saturating_sub(index).min(16);
+        if remaining == 0 {
+            break;
         }
 
-        None
+        let cursor = &keys[index..];
+        let mask = u32x16::from_slice_unaligned(cursor).eq(u32x16::splat(key)).bitmask();
+        let valid = if remaining == 16 { 0xffff } else { (1u16 << remaining) - 1 };
+        let hit = mask & valid;
+
+        if hit != 0 {
+            return Some(index + hit.trailing_zeros() as usize);
+        }
     }
+
+    None
 }
 
-#[derive(Default)]
-pub struct SimdMap8<K, V> {
+fn find_width8(keys: &[u32], len: usize, key: u32) -> Option<usize> {
+    for index in (0..keys.len()).step_by(8) {
+        let remaining = len.saturating_sub(index).min(8);
+        if remaining == 0 {
+            break;
+        }
+
+        let cursor = &keys[index..];
+        let mask = u32x8::from_slice_unaligned(cursor).eq(u32x8::splat(key)).bitmask();
+        let valid = if remaining == 8 { 0xff } else { (1u8 << remaining) - 1 };
+        let hit = mask & valid;
+
+        if hit != 0 {
+            return Some(index + hit.trailing_zeros() as usize);
+        }
+    }
+
+    None
+}
+
+const GROUP_SIZE: usize = 16;
+const EMPTY: u8 = 0xff;
+
+fn h1(hash: u64) -> usize {
+    (hash >> 7) as usize
+}
+
+fn h2(hash: u64) -> u8 {
+    (hash & 0x7f) as u8
+}
+
+pub struct SwissMap<K, V> {
+    control: Vec<u8>,
     keys: Vec<K>,
-    next: usize,
     values: Vec<V>,
+    len: usize,
 }
 
-impl<V> Map<u32, V> for SimdMap8<u32, V> {
+impl<K, V> Default for SwissMap<K, V> {
+    fn default() -> Self {
+        SwissMap {
+            control: Vec::new(),
+            keys: Vec::new(),
+            values: Vec::new(),
+            len: 0,
+        }
+    }
+}
+
+impl<V: Default + Clone> SwissMap<u32, V> {
+    fn allocate(&mut self, num_groups: usize) {
+        self.control = vec![EMPTY; num_groups * GROUP_SIZE];
+        self.keys = vec![0; num_groups * GROUP_SIZE];
+        self.values = vec![V::default(); num_groups * GROUP_SIZE];
+    }
+
+    fn grow(&mut self) {
+        let old_control = mem::take(&mut self.control);
+        let old_keys = mem::take(&mut self.keys);
+        let old_values = mem::take(&mut self.values);
+        let new_groups = (old_control.len() / GROUP_SIZE).max(1) * 2;
+
+        self.allocate(new_groups);
+        self.len = 0;
+
+        for (index, &control) in old_control.iter().enumerate() {
+            if control != EMPTY {
+                self.insert_slot(old_keys[index], old_values[index].clone());
+            }
+        }
+    }
+
+    fn insert_slot(&mut self, key: u32, value: V) {
+        let hash = hash_of(&key);
+        let num_groups = self.control.len() / GROUP_SIZE;
+        let mut group = h1(hash) % num_groups;
+        let mut probe = 0usize;
+
+        loop {
+            let base = group * GROUP_SIZE;
+
+            if let Some(lane) = (0..GROUP_SIZE).position(|lane| self.control[base + lane] == EMPTY) {
+                self.control[base + lane] = h2(hash);
+                self.keys[base + lane] = key;
+                self.values[base + lane] = value;
+                self.len += 1;
+                return;
+            }
+
+            probe += 1;
+            group = (group + probe) % num_groups;
+        }
+    }
+}
+
+impl<V: Default + Clone> Map<u32, V> for SwissMap<u32, V> {
     fn insert(&mut self, key: u32, value: V) {
-        if self.next == self.keys.len() {
-            self.keys.extend(&[0; 8]);
+        if self.control.is_empty() {
+            self.allocate(1);
+        } else if (self.len + 1) * 8 > self.control.len() * 7 {
+            self.grow();
         }
 
-        self.keys[self.next] = key;
-        self.next += 1;
-        self.values.push(value);
+        self.insert_slot(key, value);
     }
 
     fn find(&self, key: u32) -> Option<&V> {
-        for index in (0..self.keys.len()).step_by(8) {
-            let cursor = &self.keys[index..];
-            let mask = u32x8::from_slice_unaligned(cursor).eq(u32x8::splat(key));
-            let zeros = mask.bitmask().trailing_zeros();
+        if self.control.is_empty() {
+            return None;
+        }
+
+        let hash = hash_of(&key);
+        let needle = h2(hash);
+        let num_groups = self.control.len() / GROUP_SIZE;
+        let mut group = h1(hash) % num_groups;
+        let mut probe = 0usize;
+
+        loop {
+            let base = group * GROUP_SIZE;
+            let control = u8x16::from_slice_unaligned(&self.control[base..base + GROUP_SIZE]);
+            let mut matches = control.eq(u8x16::splat(needle)).bitmask();
 
-            if zeros < 8 {
-                return self.values.get(index + zeros as usize);
+            while matches != 0 {
+                let lane = matches.trailing_zeros() as usize;
+                matches &= matches - 1;
+
+                if self.keys[base + lane] == key {
+                    return Some(&self.values[base + lane]);
+                }
+            }
+
+            if control.eq(u8x16::splat(EMPTY)).bitmask() != 0 {
+                return None;
             }
+
+            probe += 1;
+            group = (group + probe) % num_groups;
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct VecMap<V> {
+    data: Vec<Option<V>>,
+}
+
+impl<V> Map<u32, V> for VecMap<V> {
+    fn insert(&mut self, key: u32, value: V) {
+        let index = key as usize;
+
+        if index >= self.data.len() {
+            self.data.resize_with(index + 1, || None);
         }
 
-        None
+        self.data[index] = Some(value);
+    }
+
+    fn find(&self, key: u32) -> Option<&V> {
+        self.data.get(key as usize)?.as_ref()
     }
 }
 
@@ -183,15 +468,55 @@ mod tests {
         run::<BinaryMap<_, _>>();
     }
 
+    #[test]
+    fn binary_map_with_comparator() {
+        let mut map = BinaryMap::with_comparator(|a: &u32, b: &u32| b.cmp(a));
+        map.insert(5, 42);
+        map.insert(1, 43);
+        map.insert(7, 44);
+
+        assert_eq!(map.find(5), Some(&42));
+        assert_eq!(map.find(1), Some(&43));
+        assert_eq!(map.find(7), Some(&44));
+        assert_eq!(map.find(8), None);
+    }
+
     #[test]
     fn kv_map() {
         run::<KvMap<_, _>>();
     }
 
+    #[test]
+    fn index_map() {
+        run::<IndexMap<_, _>>();
+    }
+
     #[test]
     fn simd_map() {
-        run::<SimdMap16<_, _>>();
-        run::<SimdMap8<_, _>>();
+        run::<SimdMap<_, _>>();
+    }
+
+    #[test]
+    fn simd_map_key_zero_not_first() {
+        let mut map = SimdMap::default();
+        map.insert(5, "five");
+        map.insert(0, "zero");
+        map.insert(9, "nine");
+
+        assert_eq!(map.find(0), Some(&"zero"));
+        assert_eq!(map.find(5), Some(&"five"));
+        assert_eq!(map.find(9), Some(&"nine"));
+        assert_eq!(map.find(42), None);
+    }
+
+    #[test]
+    fn swiss_map() {
+        run::<SwissMap<_, _>>();
+    }
+
+    #[test]
+    fn vec_map() {
+        run::<VecMap<_>>();
     }
 
     #[test]